@@ -0,0 +1,32 @@
+// Copyright 2019 by Michael Thies <mail@mhthies.de>
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
+// specific language governing permissions and limitations under the License.
+
+//! Generic branch-and-bound result type, shared by the sequential and parallel search drivers in
+//! [`crate::caobab`].
+
+use crate::Assignment;
+
+/// The outcome of evaluating a single branch-and-bound node's relaxation.
+///
+/// The `u32` carried by both variants is the relaxation's score. For [`NodeResult::Infeasible`],
+/// it is only an optimistic upper bound: no child of this node can ever score higher, since
+/// further branching only ever adds constraints. Search drivers use this bound to prune subtrees
+/// that cannot possibly beat the current incumbent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeResult {
+    /// The relaxation already satisfies all course size constraints and is thus a feasible
+    /// candidate solution, with the given assignment and score.
+    Feasible(Assignment, u32),
+    /// The relaxation violates some course size constraint. The second field is the course that
+    /// is the best candidate for the next branching decision (see `caobab::branch`), or `None` if
+    /// there is none left to branch on.
+    Infeasible(u32, Option<usize>),
+}
@@ -0,0 +1,367 @@
+// Copyright 2019 by Michael Thies <mail@mhthies.de>
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
+// specific language governing permissions and limitations under the License.
+
+//! Course-assignment specific branch-and-bound search: turning participants' course choices into
+//! a weighted bipartite matching problem ([`precompute_problem`]), solving the relaxation of a
+//! single search node via the Hungarian algorithm ([`run_bab_node`]), and exploring the resulting
+//! search tree sequentially ([`solve`]) or with a pool of worker threads
+//! ([`parallel::solve_parallel`]).
+
+pub mod parallel;
+#[cfg(test)]
+mod tests;
+
+use crate::bab::NodeResult;
+use crate::hungarian::{self, EdgeWeight};
+use crate::{Assignment, Course, Participant};
+use ndarray::{Array1, Array2};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Edge weight added for a participant's best (first) choice course. Each subsequent choice gets
+/// one less, capped at the third choice -- participants don't get any edge (i.e. that pairing
+/// can't be selected) for a fourth or later choice.
+pub const WEIGHT_OFFSET: EdgeWeight = 10;
+
+/// The precomputed weighted bipartite graph for a set of courses and participants, ready to be
+/// handed to the Hungarian algorithm by [`run_bab_node`] for different [`BABNode`]s.
+pub struct Problem {
+    /// Edge weight of participant `x` being assigned to the `y`-th course place (see
+    /// `course_map`). Dummy participant rows (see `dummy_x`) are all-zero.
+    pub adjacency_matrix: Array2<EdgeWeight>,
+    /// Whether row `x` is a dummy participant, inserted to make the bipartite graph complete (a
+    /// course place not filled by a real participant is "filled" by a dummy instead).
+    pub dummy_x: Array1<bool>,
+    /// For each course place (column), the index of the course it belongs to.
+    pub course_map: Array1<usize>,
+    /// For each course (by index), the first course place (column) belonging to it.
+    pub inverse_course_map: Vec<usize>,
+}
+
+/// The historical fixed weighting of a participant's choices by their (0-based) rank: the first
+/// choice is worth `WEIGHT_OFFSET`, the second `WEIGHT_OFFSET - 1` and the third
+/// `WEIGHT_OFFSET - 2`. A fourth or later choice gets no weight at all. The Hungarian algorithm
+/// can still place a participant there with weight 0, and [`check_feasibility`] then accepts it
+/// as one of their choices.
+///
+/// This is the default scoring function used by [`precompute_problem`], kept around so existing
+/// callers see no change in behavior.
+pub fn default_choice_weight(rank: usize) -> EdgeWeight {
+    if rank < 3 {
+        WEIGHT_OFFSET - rank as EdgeWeight
+    } else {
+        0
+    }
+}
+
+/// Precompute the weighted bipartite matching problem for the given courses and participants,
+/// using the historical fixed three-level choice weighting (see [`default_choice_weight`]).
+pub fn precompute_problem(courses: &Vec<Course>, participants: &Vec<Participant>) -> Problem {
+    precompute_problem_with_scoring(courses, participants, default_choice_weight)
+}
+
+/// Precompute the weighted bipartite matching problem for the given courses and participants,
+/// using `choice_weight` to turn a choice's rank (its 0-based position in
+/// `Participant::choices`) into an [`EdgeWeight`]. Participants may have differing numbers of
+/// choices, of any length; a choice whose weight comes out as 0 gets weight 0 like a course the
+/// participant didn't choose. That pairing is not excluded: the Hungarian algorithm can still
+/// select it, and [`check_feasibility`] accepts it as one of the participant's choices.
+///
+/// A participant's hard constraints (see [`Participant::forced_course`] and
+/// [`Participant::forbidden_courses`]) are baked into the adjacency matrix as well: a
+/// `forced_course` locks the participant onto that course's places (with maximum weight, ignoring
+/// `choices` entirely), while a forbidden course's places are made unselectable for them (weight
+/// 0), even if it is also one of their `choices`.
+pub fn precompute_problem_with_scoring<F>(
+    courses: &Vec<Course>,
+    participants: &Vec<Participant>,
+    choice_weight: F,
+) -> Problem
+where
+    F: Fn(usize) -> EdgeWeight,
+{
+    let n = courses.iter().fold(0, |acc, c| acc + c.num_max);
+
+    let mut course_map = Array1::from_elem(n, 0usize);
+    let mut inverse_course_map = Vec::with_capacity(courses.len());
+    let mut column = 0usize;
+    for course in courses.iter() {
+        inverse_course_map.push(column);
+        for _ in 0..course.num_max {
+            course_map[column] = course.index;
+            column += 1;
+        }
+    }
+
+    let mut adjacency_matrix = Array2::from_elem((n, n), 0 as EdgeWeight);
+    let mut dummy_x = Array1::from_elem(n, false);
+    for x in participants.len()..n {
+        dummy_x[x] = true;
+    }
+    for (x, participant) in participants.iter().enumerate() {
+        if let Some(forced) = participant.forced_course {
+            let course = &courses[forced];
+            let base_column = inverse_course_map[forced];
+            for y in base_column..base_column + course.num_max {
+                adjacency_matrix[(x, y)] = EdgeWeight::MAX;
+            }
+            continue;
+        }
+
+        for (rank, &choice) in participant.choices.iter().enumerate() {
+            if participant.forbidden_courses.contains(&choice) {
+                continue;
+            }
+            let weight = choice_weight(rank);
+            if weight == 0 {
+                continue;
+            }
+            let course = &courses[choice];
+            let base_column = inverse_course_map[choice];
+            for y in base_column..base_column + course.num_max {
+                adjacency_matrix[(x, y)] = weight;
+            }
+        }
+    }
+
+    Problem {
+        adjacency_matrix,
+        dummy_x,
+        course_map,
+        inverse_course_map,
+    }
+}
+
+/// A node of the branch-and-bound search tree: a set of courses that must not be used
+/// (`cancelled_courses`) and a set of courses that must not be cancelled (`enforced_courses`).
+///
+/// `Ord` establishes a search order: nodes with fewer constraints (i.e. closer to the root) sort
+/// before more constrained ones, so a best-first search explores the least restricted, and
+/// therefore most promising, nodes first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BABNode {
+    pub cancelled_courses: Vec<usize>,
+    pub enforced_courses: Vec<usize>,
+}
+
+impl Ord for BABNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let self_constraints = self.cancelled_courses.len() + self.enforced_courses.len();
+        let other_constraints = other.cancelled_courses.len() + other.enforced_courses.len();
+        self_constraints
+            .cmp(&other_constraints)
+            .then_with(|| self.cancelled_courses.cmp(&other.cancelled_courses))
+            .then_with(|| self.enforced_courses.cmp(&other.enforced_courses))
+    }
+}
+
+impl PartialOrd for BABNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Check whether `assignment` (as computed from `problem`'s relaxation for `node`) is a feasible
+/// solution: every non-cancelled course (whether enforced or not) must reach its minimum size, and
+/// -- as a sanity check on the Hungarian algorithm's result -- every participant must have ended
+/// up in one of their choices (or, for a participant with a hard constraint, in their
+/// `forced_course`, and never in one of their `forbidden_courses`), and never in a cancelled
+/// course.
+///
+/// Returns `(is_feasible, has_invalid_choice_assignment, course_to_branch_on)`, where
+/// `course_to_branch_on` is the non-cancelled, non-*enforced* course with the largest deficit below
+/// its minimum size (the best candidate for the next branching decision), or `None` if there is
+/// none. An already enforced course is excluded from the branching candidates -- it can't be
+/// cancelled anymore, so it must not be re-picked as the course to branch on, or the "enforce"
+/// child would face the exact same deficient course with no way to resolve it. If every deficient
+/// course is already enforced, the node is genuinely infeasible and there is nothing left to branch
+/// on.
+pub fn check_feasibility(
+    courses: &Vec<Course>,
+    participants: &Vec<Participant>,
+    assignment: &Assignment,
+    node: &BABNode,
+    course_instructors: &Array1<bool>,
+) -> (bool, bool, Option<usize>) {
+    let mut invalid_choice = false;
+    for (p, participant) in participants.iter().enumerate() {
+        if course_instructors[p] {
+            continue;
+        }
+        let valid = match participant.forced_course {
+            Some(forced) => assignment[p] == forced,
+            None => {
+                participant.choices.contains(&assignment[p])
+                    && !participant.forbidden_courses.contains(&assignment[p])
+            }
+        };
+        if !valid || node.cancelled_courses.contains(&assignment[p]) {
+            invalid_choice = true;
+        }
+    }
+
+    let mut course_size = vec![0usize; courses.len()];
+    for (p, &c) in assignment.iter().enumerate() {
+        if !course_instructors[p] {
+            course_size[c] += 1;
+        }
+    }
+
+    let mut feasible = true;
+    let mut worst_course = None;
+    let mut worst_deficit = 0usize;
+    for (c, course) in courses.iter().enumerate() {
+        if node.cancelled_courses.contains(&c) {
+            continue;
+        }
+        if course_size[c] < course.num_min {
+            feasible = false;
+            if node.enforced_courses.contains(&c) {
+                continue;
+            }
+            let deficit = course.num_min - course_size[c];
+            if deficit > worst_deficit {
+                worst_deficit = deficit;
+                worst_course = Some(c);
+            }
+        }
+    }
+
+    (feasible, invalid_choice, worst_course)
+}
+
+/// Solve the Hungarian-algorithm relaxation of `node` and check whether it is feasible.
+pub fn run_bab_node(
+    courses: &Vec<Course>,
+    participants: &Vec<Participant>,
+    problem: &Problem,
+    node: BABNode,
+) -> NodeResult {
+    let n = problem.adjacency_matrix.dim().0;
+    let mut adjacency_matrix = problem.adjacency_matrix.clone();
+    for &c in node.cancelled_courses.iter() {
+        let base_column = problem.inverse_course_map[c];
+        for y in base_column..base_column + courses[c].num_max {
+            for x in 0..n {
+                adjacency_matrix[(x, y)] = 0;
+            }
+        }
+    }
+    // A non-cancelled course's instructors must end up in one of its own places, regardless of
+    // their choices: lock their row onto it with maximum weight. A cancelled course's instructors
+    // fall back to being regular participants instead, using their precomputed choice weights.
+    for (c, course) in courses.iter().enumerate() {
+        if node.cancelled_courses.contains(&c) {
+            continue;
+        }
+        let base_column = problem.inverse_course_map[c];
+        for &instructor in course.instructors.iter() {
+            for y in base_column..base_column + course.num_max {
+                adjacency_matrix[(instructor, y)] = EdgeWeight::MAX;
+            }
+        }
+    }
+
+    let (column_of_row, _) = hungarian::hungarian_algorithm(&adjacency_matrix);
+
+    let mut assignment: Assignment = vec![0; participants.len()];
+    let mut score: u32 = 0;
+    for x in 0..participants.len() {
+        let y = column_of_row[x];
+        assignment[x] = problem.course_map[y];
+        score += adjacency_matrix[(x, y)] as u32;
+    }
+
+    let mut course_instructors = Array1::from_elem(participants.len(), false);
+    for (c, course) in courses.iter().enumerate() {
+        if !node.cancelled_courses.contains(&c) {
+            for &instructor in course.instructors.iter() {
+                course_instructors[instructor] = true;
+            }
+        }
+    }
+
+    let (feasible, invalid_choice, branch_course) =
+        check_feasibility(courses, participants, &assignment, &node, &course_instructors);
+
+    if feasible && !invalid_choice {
+        NodeResult::Feasible(assignment, score)
+    } else {
+        NodeResult::Infeasible(score, branch_course)
+    }
+}
+
+/// Branch a node on `course`: one child cancels it, the other enforces it. A child that would
+/// contradict or repeat the parent node's constraints (e.g. cancelling an already enforced course,
+/// or enforcing an already enforced one) is omitted. If `course` is already enforced, it is a dead
+/// end: it is still deficient (or it wouldn't have been picked), but it can't be cancelled, and
+/// enforcing it again wouldn't change anything, so no children are produced at all. The same holds
+/// for an already cancelled `course`.
+fn branch(node: &BABNode, course: usize) -> Vec<BABNode> {
+    let mut children = Vec::with_capacity(2);
+    if node.enforced_courses.contains(&course) || node.cancelled_courses.contains(&course) {
+        return children;
+    }
+    let mut cancelled_courses = node.cancelled_courses.clone();
+    cancelled_courses.push(course);
+    children.push(BABNode {
+        cancelled_courses,
+        enforced_courses: node.enforced_courses.clone(),
+    });
+    if !node.cancelled_courses.contains(&course) {
+        let mut enforced_courses = node.enforced_courses.clone();
+        enforced_courses.push(course);
+        children.push(BABNode {
+            cancelled_courses: node.cancelled_courses.clone(),
+            enforced_courses,
+        });
+    }
+    children
+}
+
+/// Sequentially search the branch-and-bound tree rooted at the node with no cancelled or enforced
+/// courses, and return the best feasible assignment found, together with its score.
+pub fn solve(
+    courses: &Vec<Course>,
+    participants: &Vec<Participant>,
+    problem: &Problem,
+) -> Option<(Assignment, u32)> {
+    let mut queue = BinaryHeap::new();
+    queue.push(BABNode {
+        cancelled_courses: vec![],
+        enforced_courses: vec![],
+    });
+
+    let mut best: Option<(Assignment, u32)> = None;
+    while let Some(node) = queue.pop() {
+        match run_bab_node(courses, participants, problem, node.clone()) {
+            NodeResult::Feasible(assignment, score) => {
+                if best
+                    .as_ref()
+                    .map_or(true, |(_, best_score)| score > *best_score)
+                {
+                    best = Some((assignment, score));
+                }
+            }
+            NodeResult::Infeasible(bound, branch_course) => {
+                let incumbent = best.as_ref().map_or(0, |(_, score)| *score);
+                if bound > incumbent {
+                    if let Some(course) = branch_course {
+                        for child in branch(&node, course) {
+                            queue.push(child);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    best
+}
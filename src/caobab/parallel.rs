@@ -0,0 +1,133 @@
+// Copyright 2019 by Michael Thies <mail@mhthies.de>
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
+// specific language governing permissions and limitations under the License.
+
+//! A parallel branch-and-bound search driver: a pool of worker threads shares a single
+//! synchronized priority queue of open [`BABNode`]s and a single synchronized incumbent score, so
+//! the search tree can be explored across multiple CPU cores instead of one node at a time (as
+//! [`super::solve`] does).
+
+use super::{branch, run_bab_node, BABNode, Problem};
+use crate::bab::NodeResult;
+use crate::{Assignment, Course, Participant};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Search the branch-and-bound tree rooted at the node with no cancelled or enforced courses,
+/// using `num_threads` worker threads that share a synchronized priority queue of open nodes and a
+/// synchronized incumbent score.
+///
+/// Each worker repeatedly pops the most promising open node (per [`BABNode`]'s `Ord`) and
+/// evaluates it via [`super::run_bab_node`]:
+/// - if it is feasible, the incumbent is updated when its score beats the current best (the
+///   incumbent score only ever increases, and is read *before* a node is branched further so other
+///   workers can prune against it as early as possible);
+/// - if it is infeasible, its children (see [`super::branch`]) are pushed back onto the queue,
+///   unless its score bound does not exceed the current incumbent, in which case the whole subtree
+///   is pruned without being explored.
+///
+/// The search terminates once the queue is empty and no worker is still evaluating a node that
+/// could produce more work.
+pub fn solve_parallel(
+    courses: &Vec<Course>,
+    participants: &Vec<Participant>,
+    problem: &Problem,
+    num_threads: usize,
+) -> Option<(Assignment, u32)> {
+    let courses = Arc::new(courses.clone());
+    let participants = Arc::new(participants.clone());
+    let problem = Arc::new(clone_problem(problem));
+
+    let queue: Arc<Mutex<BinaryHeap<Reverse<BABNode>>>> = Arc::new(Mutex::new(BinaryHeap::new()));
+    queue.lock().unwrap().push(Reverse(BABNode {
+        cancelled_courses: vec![],
+        enforced_courses: vec![],
+    }));
+
+    // Number of nodes that are either queued or currently being evaluated by a worker. The search
+    // is done once this reaches zero: the queue is empty and nobody is working on a node that
+    // could still produce children.
+    let outstanding = Arc::new(AtomicUsize::new(1));
+    let incumbent_score = Arc::new(AtomicU32::new(0));
+    let incumbent: Arc<Mutex<Option<(Assignment, u32)>>> = Arc::new(Mutex::new(None));
+
+    let handles: Vec<_> = (0..num_threads.max(1))
+        .map(|_| {
+            let courses = Arc::clone(&courses);
+            let participants = Arc::clone(&participants);
+            let problem = Arc::clone(&problem);
+            let queue = Arc::clone(&queue);
+            let outstanding = Arc::clone(&outstanding);
+            let incumbent_score = Arc::clone(&incumbent_score);
+            let incumbent = Arc::clone(&incumbent);
+
+            thread::spawn(move || loop {
+                if outstanding.load(AtomicOrdering::SeqCst) == 0 {
+                    return;
+                }
+                let node = match queue.lock().unwrap().pop() {
+                    Some(Reverse(node)) => node,
+                    None => {
+                        thread::sleep(Duration::from_micros(50));
+                        continue;
+                    }
+                };
+
+                match run_bab_node(&courses, &participants, &problem, node.clone()) {
+                    NodeResult::Feasible(assignment, score) => {
+                        if score > incumbent_score.load(AtomicOrdering::SeqCst) {
+                            let mut incumbent = incumbent.lock().unwrap();
+                            if incumbent.as_ref().map_or(true, |(_, best)| score > *best) {
+                                incumbent_score.store(score, AtomicOrdering::SeqCst);
+                                *incumbent = Some((assignment, score));
+                            }
+                        }
+                        outstanding.fetch_sub(1, AtomicOrdering::SeqCst);
+                    }
+                    NodeResult::Infeasible(bound, branch_course) => {
+                        let children = match branch_course {
+                            Some(course) if bound > incumbent_score.load(AtomicOrdering::SeqCst) => {
+                                branch(&node, course)
+                            }
+                            _ => vec![],
+                        };
+                        outstanding.fetch_add(children.len(), AtomicOrdering::SeqCst);
+                        {
+                            let mut queue = queue.lock().unwrap();
+                            for child in children {
+                                queue.push(Reverse(child));
+                            }
+                        }
+                        outstanding.fetch_sub(1, AtomicOrdering::SeqCst);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    Arc::try_unwrap(incumbent).ok().unwrap().into_inner().unwrap()
+}
+
+fn clone_problem(problem: &Problem) -> Problem {
+    Problem {
+        adjacency_matrix: problem.adjacency_matrix.clone(),
+        dummy_x: problem.dummy_x.clone(),
+        course_map: problem.course_map.clone(),
+        inverse_course_map: problem.inverse_course_map.clone(),
+    }
+}
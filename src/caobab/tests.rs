@@ -14,36 +14,48 @@ fn create_simple_problem() -> (Vec<Participant>, Vec<Course>) {
                 dbid: 0,
                 name: String::from("Participant 0"),
                 choices: vec![1, 2],
+                forced_course: None,
+                forbidden_courses: vec![],
             },
             Participant {
                 index: 1,
                 dbid: 1,
                 name: String::from("Participant 1"),
                 choices: vec![0, 2],
+                forced_course: None,
+                forbidden_courses: vec![],
             },
             Participant {
                 index: 2,
                 dbid: 2,
                 name: String::from("Participant 2"),
                 choices: vec![0, 1],
+                forced_course: None,
+                forbidden_courses: vec![],
             },
             Participant {
                 index: 3,
                 dbid: 3,
                 name: String::from("Participant 3"),
                 choices: vec![0, 1],
+                forced_course: None,
+                forbidden_courses: vec![],
             },
             Participant {
                 index: 4,
                 dbid: 4,
                 name: String::from("Participant 4"),
                 choices: vec![0, 2],
+                forced_course: None,
+                forbidden_courses: vec![],
             },
             Participant {
                 index: 5,
                 dbid: 5,
                 name: String::from("Participant 5"),
                 choices: vec![1, 2],
+                forced_course: None,
+                forbidden_courses: vec![],
             },
         ],
         vec![
@@ -51,7 +63,7 @@ fn create_simple_problem() -> (Vec<Participant>, Vec<Course>) {
                 index: 0,
                 dbid: 0,
                 name: String::from("Wanted Course 0"),
-                num_max: 2,
+                num_max: 3,
                 num_min: 2,
                 instructors: vec![0],
             },
@@ -143,6 +155,38 @@ fn test_precompute_problem() {
     }
 }
 
+#[test]
+fn test_precompute_problem_with_scoring() {
+    let (participants, courses) = create_simple_problem();
+
+    // A linear scoring function that also gives weight to a (hypothetical) fourth choice, unlike
+    // the fixed default weighting.
+    let linear_weight = |rank: usize| -> u16 {
+        if rank < 4 {
+            20 - 5 * rank as u16
+        } else {
+            0
+        }
+    };
+    let problem = super::precompute_problem_with_scoring(&courses, &participants, linear_weight);
+
+    for (x, p) in participants.iter().enumerate() {
+        for y in 0..problem.adjacency_matrix.dim().1 {
+            let choice = p.choices.iter().position(|c| *c == problem.course_map[y]);
+            assert_eq!(
+                problem.adjacency_matrix[(x, y)],
+                match choice {
+                    Some(c) => linear_weight(c),
+                    None => 0,
+                },
+                "Edge weight for participant {} with course place {} is not expected.",
+                x,
+                y
+            );
+        }
+    }
+}
+
 #[test]
 fn test_babnode_sorting() {
     let node0 = BABNode {
@@ -291,15 +335,44 @@ fn check_assignment(
     // Feasible solutions must not have wrong assigned participants
     for (p, participant) in participants.iter().enumerate() {
         if !course_instructors[p] {
+            match participant.forced_course {
+                Some(forced) => assert_eq!(
+                    assignment[p], forced,
+                    "Participant {} should be forced into course {} but is assigned to {}",
+                    p, forced, assignment[p]
+                ),
+                None => assert!(
+                    participant.choices.contains(&assignment[p]),
+                    "Course {} of participant {} is none of their choices ({:?})",
+                    assignment[p], p, participant.choices
+                ),
+            }
             assert!(
-                participant.choices.contains(&assignment[p]),
-                "Course {} of participant {} is none of their choices ({:?})",
-                assignment[p], p, participant.choices
+                !participant.forbidden_courses.contains(&assignment[p]),
+                "Participant {} is assigned to forbidden course {}",
+                p, assignment[p]
             );
         }
     }
 }
 
+#[test]
+fn test_hard_constraints() {
+    let (mut participants, courses) = create_simple_problem();
+
+    // Force Participant 3 into Course 2, even though it's none of their choices.
+    participants[3].forced_course = Some(2);
+    // Forbid Participant 4 from Course 0, their first choice, leaving only Course 2.
+    participants[4].forbidden_courses = vec![0];
+
+    let problem = super::precompute_problem(&courses, &participants);
+    let (assignment, _score) =
+        super::solve(&courses, &participants, &problem).expect("a feasible solution should exist");
+
+    assert_eq!(assignment[3], 2, "Participant 3 should have been forced into Course 2");
+    assert_ne!(assignment[4], 0, "Participant 4 should not have been assigned to the forbidden Course 0");
+}
+
 #[test]
 fn test_bab_node_simple() {
     // This test depends on `precompute_problem()`, `check_feasibility()` and `hungarian::hungarian_algorithm()`,
@@ -325,6 +398,157 @@ fn test_bab_node_simple() {
 
 }
 
+/// Brute-force the best achievable score by trying every subset of courses as the cancelled set
+/// (with no enforced courses), used as an independent optimality baseline for [`super::solve`] and
+/// [`super::parallel::solve_parallel`] in the tests below.
+fn brute_force_best_score(
+    courses: &Vec<Course>,
+    participants: &Vec<Participant>,
+    problem: &super::Problem,
+) -> Option<u32> {
+    let mut best: Option<u32> = None;
+    for mask in 0..(1u32 << courses.len()) {
+        let cancelled_courses: Vec<usize> =
+            (0..courses.len()).filter(|c| mask & (1 << c) != 0).collect();
+        let node = BABNode {
+            cancelled_courses,
+            enforced_courses: vec![],
+        };
+        if let NodeResult::Feasible(_, score) =
+            super::run_bab_node(courses, participants, problem, node)
+        {
+            best = Some(best.map_or(score, |b| b.max(score)));
+        }
+    }
+    best
+}
+
+#[test]
+fn test_solve_parallel_matches_sequential() {
+    let (participants, courses) = create_simple_problem();
+    let problem = super::precompute_problem(&courses, &participants);
+
+    let (sequential_assignment, sequential_score) =
+        super::solve(&courses, &participants, &problem).expect("sequential solve should succeed");
+    let (parallel_assignment, parallel_score) =
+        super::parallel::solve_parallel(&courses, &participants, &problem, 4)
+            .expect("parallel solve should succeed");
+
+    assert_eq!(sequential_score, parallel_score);
+
+    // Neither driver just happens to agree on a suboptimal score: compare both against an
+    // independent brute-force baseline over all cancellation subsets.
+    let best_score = brute_force_best_score(&courses, &participants, &problem)
+        .expect("a feasible solution should exist");
+    assert_eq!(sequential_score, best_score);
+    assert_eq!(parallel_score, best_score);
+
+    // Derive which courses ended up cancelled (no participants assigned) from the result itself,
+    // so `check_assignment` can verify the remaining invariants regardless of which course the
+    // search chose to cancel.
+    let mut used_courses = vec![false; courses.len()];
+    for &c in sequential_assignment.iter() {
+        used_courses[c] = true;
+    }
+    let sequential_cancelled_courses: Vec<usize> =
+        (0..courses.len()).filter(|c| !used_courses[*c]).collect();
+    check_assignment(
+        &courses,
+        &participants,
+        &sequential_assignment,
+        &BABNode {
+            cancelled_courses: sequential_cancelled_courses,
+            enforced_courses: vec![],
+        },
+    );
+
+    let mut used_courses = vec![false; courses.len()];
+    for &c in parallel_assignment.iter() {
+        used_courses[c] = true;
+    }
+    let parallel_cancelled_courses: Vec<usize> =
+        (0..courses.len()).filter(|c| !used_courses[*c]).collect();
+    check_assignment(
+        &courses,
+        &participants,
+        &parallel_assignment,
+        &BABNode {
+            cancelled_courses: parallel_cancelled_courses,
+            enforced_courses: vec![],
+        },
+    );
+}
+
+/// Regression test for a search incompleteness where the "enforce" branch of a deficient,
+/// already-enforced course re-selected the very same course to branch on next, producing a dead
+/// end instead of exploring cancellation of a *different* course. With four symmetric courses and
+/// choices that pair them up, cancelling any single course leaves exactly one other course
+/// deficient by one instructor-less participant; the search has to commit to enforcing the
+/// first-found deficient course and branch on a second one to find the best feasible assignment.
+#[test]
+fn test_solve_explores_alternative_course_cancellation() {
+    let courses: Vec<Course> = (0..4)
+        .map(|i| Course {
+            index: i,
+            dbid: i as u64,
+            name: format!("Course {}", i),
+            num_max: 4,
+            num_min: 2,
+            instructors: vec![i],
+        })
+        .collect();
+
+    let choices = vec![
+        vec![0, 1],
+        vec![0, 1],
+        vec![2, 3],
+        vec![2, 3],
+        vec![0, 2],
+        vec![1, 3],
+        vec![0, 3],
+        vec![1, 2],
+    ];
+    let participants: Vec<Participant> = choices
+        .into_iter()
+        .enumerate()
+        .map(|(i, choices)| Participant {
+            index: i,
+            dbid: i as u64,
+            name: format!("Participant {}", i),
+            choices,
+            forced_course: None,
+            forbidden_courses: vec![],
+        })
+        .collect();
+
+    let problem = super::precompute_problem(&courses, &participants);
+
+    let best_score = brute_force_best_score(&courses, &participants, &problem)
+        .expect("a feasible solution should exist");
+    let (assignment, score) =
+        super::solve(&courses, &participants, &problem).expect("solve should find a solution");
+    assert_eq!(
+        score, best_score,
+        "solve() should find the brute-force optimal score, not get stuck on one cancellation"
+    );
+
+    let mut used_courses = vec![false; courses.len()];
+    for &c in assignment.iter() {
+        used_courses[c] = true;
+    }
+    let cancelled_courses: Vec<usize> =
+        (0..courses.len()).filter(|c| !used_courses[*c]).collect();
+    check_assignment(
+        &courses,
+        &participants,
+        &assignment,
+        &BABNode {
+            cancelled_courses,
+            enforced_courses: vec![],
+        },
+    );
+}
+
 // TODO test run_bab_node with large problem
 
 // TODO test solve with simple problem
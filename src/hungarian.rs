@@ -0,0 +1,103 @@
+// Copyright 2019 by Michael Thies <mail@mhthies.de>
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
+// specific language governing permissions and limitations under the License.
+
+//! Implementation of the Hungarian algorithm (Kuhn-Munkres method) for finding a maximum weight
+//! perfect matching in a complete bipartite graph, as used by [`crate::caobab`] to solve the
+//! relaxation of a single branch-and-bound node.
+
+use ndarray::Array2;
+
+/// Type used for edge weights in the adjacency matrix handed to [`hungarian_algorithm`]. The total
+/// weight of the resulting matching is returned as a `u32`.
+pub type EdgeWeight = u16;
+
+/// Find a maximum weight perfect matching in a complete bipartite graph, given as a square
+/// adjacency matrix of edge weights.
+///
+/// Returns, for each row, the index of the column it got matched to, together with the total
+/// weight of the matching. Runs in O(n^3) time.
+pub fn hungarian_algorithm(adjacency_matrix: &Array2<EdgeWeight>) -> (Vec<usize>, u32) {
+    let n = adjacency_matrix.dim().0;
+    assert_eq!(
+        n,
+        adjacency_matrix.dim().1,
+        "adjacency matrix must be square"
+    );
+
+    // The classic Hungarian method minimizes cost; we are looking for a maximum weight matching,
+    // so we minimize `cost = max_weight - weight` instead. All arrays below are 1-indexed (index 0
+    // is a sentinel for "unmatched"), as is customary for this algorithm.
+    let max_weight = adjacency_matrix.iter().copied().max().unwrap_or(0) as i64;
+    let cost = |i: usize, j: usize| max_weight - adjacency_matrix[(i, j)] as i64;
+    let inf = i64::MAX / 2;
+
+    let mut u = vec![0i64; n + 1];
+    let mut v = vec![0i64; n + 1];
+    let mut p = vec![0usize; n + 1];
+    let mut way = vec![0usize; n + 1];
+
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0usize;
+        let mut minv = vec![inf; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = inf;
+            let mut j1 = 0usize;
+            for j in 1..=n {
+                if !used[j] {
+                    let cur = cost(i0 - 1, j - 1) - u[i0] - v[j];
+                    if cur < minv[j] {
+                        minv[j] = cur;
+                        way[j] = j0;
+                    }
+                    if minv[j] < delta {
+                        delta = minv[j];
+                        j1 = j;
+                    }
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut result = vec![0usize; n];
+    let mut total: u32 = 0;
+    for j in 1..=n {
+        if p[j] != 0 {
+            let i = p[j] - 1;
+            result[i] = j - 1;
+            total += adjacency_matrix[(i, j - 1)] as u32;
+        }
+    }
+    (result, total)
+}
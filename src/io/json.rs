@@ -0,0 +1,206 @@
+// Copyright 2019 by Michael Thies <mail@mhthies.de>
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
+// specific language governing permissions and limitations under the License.
+
+//! Machine-readable JSON representation of a calculated course assignment (see
+//! [`write_assignment`]) and of the problem input (see [`read_problem`]).
+//!
+//! In contrast to [`super::format_assignment`]'s human-readable text, this is meant to be consumed
+//! by external tooling -- e.g. a web frontend -- so `cdecao` can be driven as a subprocess of a
+//! larger pipeline instead of having its output screen-scraped.
+
+use super::super::{Assignment, Course, Participant};
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// A single participant within a [`CourseResult`], as it appears in the JSON output.
+#[derive(Debug, Serialize)]
+struct ParticipantResult<'a> {
+    dbid: u64,
+    name: &'a str,
+    instructor: bool,
+}
+
+/// A single course within an [`AssignmentResult`], as it appears in the JSON output.
+#[derive(Debug, Serialize)]
+struct CourseResult<'a> {
+    dbid: u64,
+    name: &'a str,
+    cancelled: bool,
+    participants: Vec<ParticipantResult<'a>>,
+}
+
+/// Top-level JSON document produced by [`write_assignment`].
+#[derive(Debug, Serialize)]
+struct AssignmentResult<'a> {
+    score: u32,
+    courses: Vec<CourseResult<'a>>,
+}
+
+/// Serialize the calculated course assignment into a stable JSON schema and write it to `writer`.
+///
+/// The output includes, per course, its resulting participant list (with instructor flags) and
+/// whether it had to be cancelled (no participants assigned to it), plus the achieved objective
+/// `score`, so external tooling can consume the result without parsing
+/// [`super::format_assignment`]'s human-readable text.
+pub fn write_assignment<W: Write>(
+    writer: W,
+    assignment: &Assignment,
+    score: u32,
+    courses: &Vec<Course>,
+    participants: &Vec<Participant>,
+) -> serde_json::Result<()> {
+    let courses_result: Vec<CourseResult> = courses
+        .iter()
+        .map(|c| {
+            let participants_result: Vec<ParticipantResult> = assignment
+                .iter()
+                .enumerate()
+                .filter(|(_, ac)| **ac == c.index)
+                .map(|(ap, _)| ParticipantResult {
+                    dbid: participants[ap].dbid,
+                    name: &participants[ap].name,
+                    instructor: c.instructors.contains(&ap),
+                })
+                .collect();
+            CourseResult {
+                dbid: c.dbid,
+                name: &c.name,
+                cancelled: participants_result.is_empty(),
+                participants: participants_result,
+            }
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(
+        writer,
+        &AssignmentResult {
+            score,
+            courses: courses_result,
+        },
+    )
+}
+
+/// A single participant as read from the JSON input document (see [`read_problem`]).
+#[derive(Debug, Deserialize)]
+struct ParticipantInput {
+    dbid: u64,
+    name: String,
+    choices: Vec<usize>,
+    #[serde(default)]
+    forced_course: Option<usize>,
+    #[serde(default)]
+    forbidden_courses: Vec<usize>,
+}
+
+/// A single course as read from the JSON input document (see [`read_problem`]).
+#[derive(Debug, Deserialize)]
+struct CourseInput {
+    dbid: u64,
+    name: String,
+    num_max: usize,
+    num_min: usize,
+    instructors: Vec<usize>,
+}
+
+/// Top-level JSON document consumed by [`read_problem`].
+#[derive(Debug, Deserialize)]
+struct ProblemInput {
+    courses: Vec<CourseInput>,
+    participants: Vec<ParticipantInput>,
+}
+
+/// Read a list of courses and participants from a JSON document, assigning their `index` fields by
+/// position in the respective list (courses first, so a participant's `choices` can already refer
+/// to them).
+///
+/// This is the matching input format for [`write_assignment`]'s output, allowing `cdecao` to be
+/// driven as a subprocess in a larger pipeline instead of only reading the CdEDB-specific export
+/// format handled by [`super::cdedb`].
+///
+/// All course and participant indices referenced by `choices`, `forced_course`,
+/// `forbidden_courses` and `instructors` are validated to be in range before the result is
+/// returned, so a structurally valid but semantically broken document (e.g. a choice referring to
+/// a non-existent course) is rejected here with a descriptive error, rather than panicking later
+/// on an out-of-bounds access in [`super::super::caobab::precompute_problem`].
+pub fn read_problem<R: Read>(reader: R) -> serde_json::Result<(Vec<Course>, Vec<Participant>)> {
+    let input: ProblemInput = serde_json::from_reader(reader)?;
+
+    let num_courses = input.courses.len();
+    let num_participants = input.participants.len();
+
+    for (i, p) in input.participants.iter().enumerate() {
+        for &choice in p.choices.iter() {
+            if choice >= num_courses {
+                return Err(serde_json::Error::custom(format!(
+                    "choice {} of participant {} ({}) does not refer to a known course",
+                    choice, i, p.name
+                )));
+            }
+        }
+        if let Some(forced) = p.forced_course {
+            if forced >= num_courses {
+                return Err(serde_json::Error::custom(format!(
+                    "forced_course {} of participant {} ({}) does not refer to a known course",
+                    forced, i, p.name
+                )));
+            }
+        }
+        for &forbidden in p.forbidden_courses.iter() {
+            if forbidden >= num_courses {
+                return Err(serde_json::Error::custom(format!(
+                    "forbidden course {} of participant {} ({}) does not refer to a known course",
+                    forbidden, i, p.name
+                )));
+            }
+        }
+    }
+    for (i, c) in input.courses.iter().enumerate() {
+        for &instructor in c.instructors.iter() {
+            if instructor >= num_participants {
+                return Err(serde_json::Error::custom(format!(
+                    "instructor {} of course {} ({}) does not refer to a known participant",
+                    instructor, i, c.name
+                )));
+            }
+        }
+    }
+
+    let courses = input
+        .courses
+        .into_iter()
+        .enumerate()
+        .map(|(index, c)| Course {
+            index,
+            dbid: c.dbid,
+            name: c.name,
+            num_max: c.num_max,
+            num_min: c.num_min,
+            instructors: c.instructors,
+        })
+        .collect();
+
+    let participants = input
+        .participants
+        .into_iter()
+        .enumerate()
+        .map(|(index, p)| Participant {
+            index,
+            dbid: p.dbid,
+            name: p.name,
+            choices: p.choices,
+            forced_course: p.forced_course,
+            forbidden_courses: p.forbidden_courses,
+        })
+        .collect();
+
+    Ok((courses, participants))
+}
@@ -0,0 +1,95 @@
+// Copyright 2019 by Michael Thies <mail@mhthies.de>
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the License for the
+// specific language governing permissions and limitations under the License.
+
+mod bab;
+mod caobab;
+mod hungarian;
+mod io;
+
+use std::env;
+use std::fs::File;
+use std::process;
+
+/// A participant of the academy who needs to be assigned to one of their chosen courses.
+#[derive(Debug, Clone)]
+pub struct Participant {
+    pub index: usize,
+    pub dbid: u64,
+    pub name: String,
+    pub choices: Vec<usize>,
+    /// If set, this participant must be assigned to this course, regardless of `choices` (e.g. to
+    /// pin a participant to a specific course due to a prior attendance or a conflict elsewhere).
+    pub forced_course: Option<usize>,
+    /// Courses this participant must not be assigned to (e.g. due to a conflict or a split
+    /// friend-group), even if listed in `choices`.
+    pub forbidden_courses: Vec<usize>,
+}
+
+/// A course that can be filled with participants and needs to be staffed by its instructors.
+#[derive(Debug, Clone)]
+pub struct Course {
+    pub index: usize,
+    pub dbid: u64,
+    pub name: String,
+    pub num_max: usize,
+    pub num_min: usize,
+    pub instructors: Vec<usize>,
+}
+
+/// For each participant (by index), the index of the course they have been assigned to.
+pub type Assignment = Vec<usize>;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <input file> [--json]", args[0]);
+        process::exit(1);
+    }
+    let json_output = args.iter().skip(2).any(|a| a == "--json");
+
+    let input_file = File::open(&args[1]).unwrap_or_else(|e| {
+        eprintln!("Could not open input file {}: {}", args[1], e);
+        process::exit(1);
+    });
+    let (courses, participants) = io::json::read_problem(input_file).unwrap_or_else(|e| {
+        eprintln!("Could not parse input file {}: {}", args[1], e);
+        process::exit(1);
+    });
+
+    let problem = caobab::precompute_problem(&courses, &participants);
+    let num_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let result = caobab::parallel::solve_parallel(&courses, &participants, &problem, num_threads);
+
+    match result {
+        Some((assignment, score)) => {
+            if json_output {
+                io::json::write_assignment(
+                    std::io::stdout(),
+                    &assignment,
+                    score,
+                    &courses,
+                    &participants,
+                )
+                .unwrap_or_else(|e| {
+                    eprintln!("Could not serialize the result to JSON: {}", e);
+                    process::exit(1);
+                });
+            } else {
+                println!("{}", io::format_assignment(&assignment, &courses, &participants));
+            }
+            eprintln!("Achieved score: {}", score);
+        }
+        None => {
+            eprintln!("No feasible course assignment found.");
+            process::exit(1);
+        }
+    }
+}